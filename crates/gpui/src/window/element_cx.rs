@@ -35,17 +35,43 @@ use crate::{
     Bounds, BoxShadow, ContentMask, Corners, CursorStyle, DevicePixels, DispatchNodeId,
     DispatchPhase, DispatchTree, DrawPhase, ElementId, ElementStateBox, EntityId, FocusHandle,
     FocusId, FontId, GlobalElementId, GlyphId, Hsla, ImageData, InputHandler, IsZero, KeyContext,
-    KeyEvent, LayoutId, LineLayoutIndex, ModifiersChangedEvent, MonochromeSprite, MouseEvent,
-    PaintQuad, Path, Pixels, PlatformInputHandler, Point, PolychromeSprite, Quad,
-    RenderGlyphParams, RenderImageParams, RenderSvgParams, Scene, Shadow, SharedString, Size,
-    StrikethroughStyle, Style, Task, TextStyleRefinement, TransformationMatrix, Underline,
-    UnderlineStyle, Window, WindowContext, SUBPIXEL_VARIANTS,
+    KeyEvent, LayoutId, LineLayoutIndex, ModifiersChangedEvent, MouseEvent, PaintQuad, Path,
+    PathBuilder, Pixels, PlatformInputHandler, Point, Quad, RenderGlyphParams, RenderImageParams,
+    RenderSvgParams, Rgba, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style, Task,
+    TextStyleRefinement, TransformationMatrix, Underline, UnderlineStyle, Window, WindowContext,
+    SUBPIXEL_VARIANTS,
 };
 
+/// The context passed to an element's [`Element::paint`](crate::Element::paint), including
+/// the one invoked by a [`Canvas`](crate::Canvas) paint callback — an alias for
+/// [`ElementContext`] naming the phase it's used in.
+pub type PaintContext<'a> = ElementContext<'a>;
+
+/// The context passed to an element's [`Element::prepaint`](crate::Element::prepaint) —
+/// an alias for [`ElementContext`] naming the phase it's used in.
+pub type PrepaintContext<'a> = ElementContext<'a>;
+
+/// The context passed to an element's
+/// [`Element::request_layout`](crate::Element::request_layout) — an alias for
+/// [`ElementContext`] naming the phase it's used in.
+pub type RequestLayoutContext<'a> = ElementContext<'a>;
+
 /// This context is used for assisting in the implementation of the element trait
 #[derive(Deref, DerefMut)]
 pub struct ElementContext<'a> {
+    #[deref]
+    #[deref_mut]
     pub(crate) cx: WindowContext<'a>,
+    /// The stack of transforms pushed by [`Self::with_transform`], composed innermost
+    /// last. Lives here rather than on `Window` because nested `with_transform` calls
+    /// thread the same `&mut ElementContext` straight through the closure, so the stack
+    /// only needs to outlive one paint call, not the whole frame.
+    transform_stack: Vec<TransformationMatrix>,
+    /// The stack of non-rectangular clip paths pushed by [`Self::with_clip_path`], for
+    /// the same reason `transform_stack` lives here rather than on `Window`. Its length
+    /// is the stencil depth of the innermost active clip; the next nested clip stamps one
+    /// more than that.
+    clip_path_stack: Vec<Path<Pixels>>,
 }
 
 impl<'a> WindowContext<'a> {
@@ -55,6 +81,8 @@ impl<'a> WindowContext<'a> {
     pub fn with_element_context<R>(&mut self, f: impl FnOnce(&mut ElementContext) -> R) -> R {
         f(&mut ElementContext {
             cx: WindowContext::new(self.app, self.window),
+            transform_stack: Vec::new(),
+            clip_path_stack: Vec::new(),
         })
     }
 }
@@ -202,4 +230,968 @@ impl<'a> VisualContext for ElementContext<'a> {
     }
 }
 
-impl<'a> ElementContext<'a> {}
+/// A fill or stroke paint: either a single solid color or a gradient ramp.
+///
+/// Anywhere a fill color was previously accepted (`paint_quad`, `paint_path`, ...) now
+/// accepts `impl Into<Brush>`, so existing call sites that pass an [`Hsla`] keep working
+/// unchanged via the [`From<Hsla>`] impl below.
+///
+/// Gradient `stops` are `(offset, color)` pairs in `0.0..=1.0`, sorted by offset. The
+/// fragment shader interpolates in premultiplied-alpha space between the two stops that
+/// bracket a given point's gradient parameter `t`, clamping to the first stop's color
+/// below the first stop's offset and the last stop's color above the last stop's offset.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Brush {
+    /// A single, uniform color.
+    Solid(Hsla),
+    /// A gradient that varies linearly along the axis from `start` to `end`.
+    ///
+    /// The gradient parameter at a point `p` is
+    /// `dot(p - start, end - start) / |end - start|²`.
+    LinearGradient {
+        /// The point at which the gradient reaches offset `0.0`.
+        start: Point<Pixels>,
+        /// The point at which the gradient reaches offset `1.0`.
+        end: Point<Pixels>,
+        /// Color stops sorted by offset, each in `0.0..=1.0`.
+        stops: Vec<(f32, Hsla)>,
+    },
+    /// A gradient that varies radially outward from `center`.
+    ///
+    /// The gradient parameter at a point `p` is `|p - center| / radius`.
+    RadialGradient {
+        /// The center of the gradient, at offset `0.0`.
+        center: Point<Pixels>,
+        /// The distance from `center` at which the gradient reaches offset `1.0`.
+        radius: Pixels,
+        /// Color stops sorted by offset, each in `0.0..=1.0`.
+        stops: Vec<(f32, Hsla)>,
+    },
+}
+
+impl From<Hsla> for Brush {
+    fn from(color: Hsla) -> Self {
+        Brush::Solid(color)
+    }
+}
+
+/// A `Scene` primitive filling a quad with a linear or radial gradient rather than a flat
+/// color, produced by [`ElementContext::paint_quad`] when given a gradient [`Brush`].
+///
+/// It carries the quad's already-transformed bounds/corner-radii/content-mask alongside
+/// the gradient's control points and stop table, so the fragment shader can compute the
+/// gradient parameter `t` per-fragment (see [`gradient_parameter`]) and look up the
+/// bracketing stops (see [`sample_gradient_stops`]) instead of the color being baked in
+/// per-vertex.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientQuad {
+    /// The quad's bounds, corner radii, border, and content mask, already transformed and
+    /// scaled to device pixels.
+    pub quad: PaintQuad,
+    /// The gradient fill. Always a [`Brush::LinearGradient`] or [`Brush::RadialGradient`]
+    /// — solid fills use the plain [`PaintQuad`] primitive instead.
+    pub gradient: Brush,
+}
+
+impl GradientQuad {
+    /// The color this gradient resolves to at `point`, computed the same way the
+    /// fragment shader computes it per-fragment.
+    pub fn sample(&self, point: Point<Pixels>) -> Hsla {
+        sample_gradient_stops(gradient_stops(&self.gradient), gradient_parameter(&self.gradient, point))
+    }
+}
+
+/// Maps a [`Brush`]'s control points into scene space the same way
+/// [`ElementContext::paint_quad`] maps the
+/// quad itself, so `GradientQuad::sample` can compare a scene-space point against them.
+fn transform_brush(brush: Brush, transform: TransformationMatrix, scale_factor: f32) -> Brush {
+    let transform_point = |p: Point<Pixels>| {
+        let p = transform.apply(p);
+        point(px(p.x.0 * scale_factor), px(p.y.0 * scale_factor))
+    };
+    match brush {
+        Brush::Solid(color) => Brush::Solid(color),
+        Brush::LinearGradient { start, end, stops } => Brush::LinearGradient {
+            start: transform_point(start),
+            end: transform_point(end),
+            stops,
+        },
+        Brush::RadialGradient {
+            center,
+            radius,
+            stops,
+        } => Brush::RadialGradient {
+            center: transform_point(center),
+            radius: px(radius.0 * scale_factor),
+            stops,
+        },
+    }
+}
+
+/// The gradient parameter `t` at `point`: for a linear gradient, the projection of `point`
+/// onto the `start`-`end` axis (`dot(point - start, end - start) / |end - start|²`); for
+/// a radial gradient, the distance from `center` over `radius`. Solid brushes have no
+/// gradient parameter and always return `0.0`.
+fn gradient_parameter(gradient: &Brush, point: Point<Pixels>) -> f32 {
+    match gradient {
+        Brush::Solid(_) => 0.,
+        Brush::LinearGradient { start, end, .. } => {
+            let axis = *end - *start;
+            let axis_length_squared = axis.x.0 * axis.x.0 + axis.y.0 * axis.y.0;
+            if axis_length_squared <= 0. {
+                0.
+            } else {
+                let offset = point - *start;
+                (offset.x.0 * axis.x.0 + offset.y.0 * axis.y.0) / axis_length_squared
+            }
+        }
+        Brush::RadialGradient { center, radius, .. } => {
+            if radius.0 <= 0. {
+                0.
+            } else {
+                (point - *center).magnitude().0 / radius.0
+            }
+        }
+    }
+}
+
+/// The stop table for a gradient brush, or an empty slice for a solid brush.
+fn gradient_stops(gradient: &Brush) -> &[(f32, Hsla)] {
+    match gradient {
+        Brush::Solid(_) => &[],
+        Brush::LinearGradient { stops, .. } | Brush::RadialGradient { stops, .. } => stops,
+    }
+}
+
+/// Binary-search `stops` (sorted by offset, each in `0.0..=1.0`) for the pair bracketing
+/// `t`, then interpolate between them in premultiplied-alpha space. Clamps to the first
+/// stop's color below its offset and the last stop's color above its offset.
+fn sample_gradient_stops(stops: &[(f32, Hsla)], t: f32) -> Hsla {
+    let Some((&(first_offset, first_color), rest)) = stops.split_first() else {
+        return Hsla::transparent_black();
+    };
+    if t <= first_offset {
+        return first_color;
+    }
+    let Some(&(last_offset, last_color)) = rest.last() else {
+        return first_color;
+    };
+    if t >= last_offset {
+        return last_color;
+    }
+
+    let upper = stops
+        .binary_search_by(|(offset, _)| offset.partial_cmp(&t).unwrap())
+        .unwrap_or_else(|insert_index| insert_index);
+    if stops[upper].0 == t {
+        return stops[upper].1;
+    }
+    let (lower_offset, lower_color) = stops[upper - 1];
+    let (upper_offset, upper_color) = stops[upper];
+    let local_t = (t - lower_offset) / (upper_offset - lower_offset);
+    lerp_premultiplied(lower_color, upper_color, local_t)
+}
+
+/// Linearly interpolate two colors in premultiplied-alpha space, as the gradient shader
+/// does, so that partially transparent stops blend correctly instead of fringing toward
+/// transparent black.
+fn lerp_premultiplied(from: Hsla, to: Hsla, t: f32) -> Hsla {
+    let premultiply = |color: Rgba| Rgba {
+        r: color.r * color.a,
+        g: color.g * color.a,
+        b: color.b * color.a,
+        a: color.a,
+    };
+    let from = premultiply(from.into());
+    let to = premultiply(to.into());
+    let a = from.a + (to.a - from.a) * t;
+    let unpremultiply = |c: f32| if a > 0. { c / a } else { 0. };
+    Rgba {
+        r: unpremultiply(from.r + (to.r - from.r) * t),
+        g: unpremultiply(from.g + (to.g - from.g) * t),
+        b: unpremultiply(from.b + (to.b - from.b) * t),
+        a,
+    }
+    .into()
+}
+
+/// A `Scene` primitive that stamps `path`'s filled interior into the stencil buffer,
+/// pushed in increment/decrement pairs by [`ElementContext::with_clip_path`].
+///
+/// Content painted between a pair is tested against `stencil == depth`; since nested
+/// clips use consecutive depths, that test only passes where every enclosing clip's
+/// increment also covered the fragment, composing arbitrary clip paths the same way
+/// nested [`ContentMask`]s compose their rectangles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StencilClip {
+    /// The clip path, already transformed and scaled to device pixels.
+    pub path: Path<Pixels>,
+    /// The stencil value this clip stamps.
+    pub depth: u32,
+    /// `true` to increment the stencil where `path` covers a fragment, `false` to
+    /// decrement it back once the clipped content has been painted.
+    pub increment: bool,
+}
+
+impl<'a> ElementContext<'a> {
+    /// Paint a quad filled with the given [`Brush`], which may be a solid color or a
+    /// linear/radial gradient.
+    ///
+    /// A gradient fill is lowered to a dedicated [`GradientQuad`] `Scene` primitive that
+    /// carries the gradient's control points and stop table alongside the quad's
+    /// transformed bounds, rather than reusing the solid-fill quad primitive.
+    pub fn paint_quad(&mut self, quad: PaintQuad, brush: impl Into<Brush>) {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        let scale_factor = self.scale_factor();
+        let content_mask = self.content_mask();
+        let transform = self.transform();
+        let mut quad = quad
+            .content_mask(content_mask)
+            .transform(transform)
+            .scale(scale_factor);
+        let window = &mut *self.window;
+        match brush.into() {
+            Brush::Solid(color) => {
+                quad.background = color;
+                window.next_frame.scene.insert_primitive(quad);
+            }
+            gradient => {
+                let gradient = transform_brush(gradient, transform, scale_factor);
+                window
+                    .next_frame
+                    .scene
+                    .insert_primitive(GradientQuad { quad, gradient });
+            }
+        }
+    }
+
+    /// Paint a filled path, such as a glyph or a vector icon, with the given [`Brush`].
+    ///
+    /// Unlike [`Self::paint_quad`], no separate primitive variant is used: the brush,
+    /// gradient or solid, is stored in the `Path` primitive's own `brush` field.
+    pub fn paint_path(&mut self, mut path: Path<Pixels>, brush: impl Into<Brush>) {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        let scale_factor = self.scale_factor();
+        let content_mask = self.content_mask();
+        path.content_mask = content_mask;
+        path.brush = brush.into();
+        path.transform = self.transform();
+        let window = &mut *self.window;
+        window
+            .next_frame
+            .scene
+            .insert_primitive(path.scale(scale_factor));
+    }
+
+    /// Push a transform onto the transform stack for the duration of `f`, composing it
+    /// with any enclosing transform, so every quad, path, sprite, and glyph painted inside
+    /// the closure is drawn through it.
+    ///
+    /// Nested calls multiply matrices (the new transform is applied before the enclosing
+    /// one), and the transform is popped again once `f` returns, so sibling elements
+    /// painted afterward are unaffected. This mirrors the other `with_*` state-stack
+    /// methods on this context (see the module documentation).
+    pub fn with_transform<R>(
+        &mut self,
+        transform: TransformationMatrix,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        if transform == TransformationMatrix::unit() {
+            return f(self);
+        }
+
+        let composed = self.transform().compose(transform);
+        self.transform_stack.push(composed);
+        let result = f(self);
+        self.transform_stack.pop();
+        result
+    }
+
+    /// The transform that currently applies to painted content, composed from the
+    /// transform stack pushed by [`Self::with_transform`].
+    pub fn transform(&self) -> TransformationMatrix {
+        self.transform_stack
+            .last()
+            .copied()
+            .unwrap_or_else(TransformationMatrix::unit)
+    }
+
+    /// Restrict drawing inside `f` to the interior of `path`, composing with any
+    /// enclosing clip.
+    ///
+    /// When `path` is an axis-aligned rectangle this is equivalent to (and implemented
+    /// as) [`Self::with_content_mask`] — the cheap rectangular fast path. Otherwise the
+    /// path is transformed by [`Self::transform`] and a pair of [`StencilClip`]
+    /// primitives is pushed to the scene around `f`'s content: an increment before, and a
+    /// decrement at the same depth after. `depth` is one more than the number of clips
+    /// already on [`Self::clip_path_stack`], so content painted by `f` — which is tested
+    /// against `stencil == depth` — is visible only where every enclosing clip path also
+    /// passed, composing nested clips instead of one replacing another.
+    pub fn with_clip_path<R>(&mut self, path: Path<Pixels>, f: impl FnOnce(&mut Self) -> R) -> R {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        let scale_factor = self.scale_factor();
+        let transform = self.transform();
+        if let Some(bounds) = path.as_axis_aligned_rect() {
+            let bounds = bounds.transform(transform);
+            return self.with_content_mask(
+                Some(ContentMask {
+                    bounds: bounds.intersect(&self.content_mask().bounds),
+                }),
+                f,
+            );
+        }
+        let depth = self.clip_path_stack.len() as u32 + 1;
+        let transformed_path = path.clone().transform(transform).scale(scale_factor);
+
+        self.clip_path_stack.push(path);
+        let window = &mut *self.window;
+        window.next_frame.scene.insert_primitive(StencilClip {
+            path: transformed_path.clone(),
+            depth,
+            increment: true,
+        });
+
+        let result = f(self);
+
+        self.clip_path_stack.pop();
+        let window = &mut *self.window;
+        window.next_frame.scene.insert_primitive(StencilClip {
+            path: transformed_path,
+            depth,
+            increment: false,
+        });
+        result
+    }
+
+    /// Paint a stroked polyline, expanding it into filled geometry according to `style`
+    /// and handing the result to the existing path-fill pipeline — no new shader is
+    /// required.
+    ///
+    /// `closed` indicates whether an implicit segment should be drawn from the last point
+    /// back to the first. Without dashing, a closed stroke is joined there rather than
+    /// capped. With dashing, the closing segment is dashed like the others, and every dash
+    /// is stroked as an open run with caps at both of its ends.
+    pub fn paint_stroke(
+        &mut self,
+        polyline: &[Point<Pixels>],
+        closed: bool,
+        style: &StrokeStyle,
+        brush: impl Into<Brush>,
+    ) {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        if polyline.len() < 2 {
+            return;
+        }
+
+        let brush = brush.into();
+        for segment in dash_polyline(polyline, closed, style) {
+            let stroke_path = stroke_polyline_to_fill_path(&segment.points, segment.closed, style);
+            self.paint_path(stroke_path, brush.clone());
+        }
+    }
+
+    /// Paint an image into `bounds`, sampling it according to `options`.
+    ///
+    /// `options.source` restricts sampling to a sub-rectangle of the source image (in its
+    /// own pixel space), enabling sprite-sheet frames to be drawn from a single texture;
+    /// it defaults to the whole image. `options.tile` selects how the source rectangle's
+    /// UVs wrap past `0.0..=1.0` (clamped, repeated, or mirrored). The source rectangle is
+    /// stretched to fill `bounds`, so the tile mode only affects samples that fall past the
+    /// source's edges; the source is not repeated across `bounds`. `options.interpolation`
+    /// selects the texture sampler: nearest-neighbor for crisp pixel art and zoomed-in
+    /// inspection UIs, or bilinear for smooth scaling.
+    ///
+    /// These fields are carried by a dedicated [`ImageSprite`] `Scene` primitive, which
+    /// selects the matching sampler and UV-wrapping mode in the fragment shader; `grayscale`
+    /// picks the same monochrome-vs-polychrome tinting behavior as the existing sprite
+    /// pipeline.
+    pub fn paint_image(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        data: Arc<ImageData>,
+        options: ImagePaintOptions,
+        grayscale: bool,
+    ) -> Result<()> {
+        debug_assert_eq!(self.window.draw_phase, DrawPhase::Paint);
+        let scale_factor = self.scale_factor();
+        let content_mask = self.content_mask();
+        let transform = self.transform();
+        let source_bounds = options
+            .source
+            .unwrap_or_else(|| Bounds::new(Point::default(), data.size()));
+
+        let window = &mut *self.window;
+        window.next_frame.scene.insert_primitive(ImageSprite {
+            bounds: bounds.scale(scale_factor),
+            content_mask: content_mask.scale(scale_factor),
+            corner_radii: corner_radii.scale(scale_factor),
+            transform,
+            data,
+            source_bounds,
+            interpolation: options.interpolation,
+            tile: options.tile,
+            grayscale,
+        });
+        Ok(())
+    }
+}
+
+/// How samples that fall past the edges of an image's source sub-rectangle are resolved,
+/// such as the neighbors [`InterpolationMode::Bilinear`] reads at the source's edge. The
+/// source is stretched to fill the destination bounds on each axis (see
+/// [`ImageSprite::sample_uv`]), so this mode does not repeat it across the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TileMode {
+    /// Samples past the source edge clamp to the edge pixel. The default.
+    #[default]
+    Clamp,
+    /// Samples past the source edge wrap around to the opposite edge.
+    Repeat,
+    /// Samples past the source edge reflect back into the source, producing a seamless
+    /// tile at the boundary.
+    Mirror,
+}
+
+/// How an image's texels are sampled when the source and destination rectangles differ in
+/// size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InterpolationMode {
+    /// Sample the nearest texel. Preserves hard edges, essential for pixel-art and
+    /// zoomed-in inspection UIs.
+    Nearest,
+    /// Linearly interpolate between the four nearest texels. The default.
+    #[default]
+    Bilinear,
+}
+
+/// Sampling options for [`ElementContext::paint_image`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImagePaintOptions {
+    /// Restrict sampling to this sub-rectangle of the source image, in the source's own
+    /// pixel space. `None` samples the whole image.
+    pub source: Option<Bounds<Pixels>>,
+    /// How sampling extends past the edges of `source`.
+    pub tile: TileMode,
+    /// The texture sampler used to map destination pixels back to `source` texels.
+    pub interpolation: InterpolationMode,
+}
+
+/// A `Scene` primitive painting an image, produced by [`ElementContext::paint_image`].
+///
+/// This carries its own `source_bounds`/`interpolation`/`tile` fields rather than being
+/// threaded through the existing `MonochromeSprite`/`PolychromeSprite` primitives, since
+/// those types are defined outside this module and adding fields to them is out of scope
+/// here; `grayscale` records which of the two sampling/tinting behaviors the fragment
+/// shader should use in their place.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSprite {
+    /// The destination bounds, already scaled to device pixels.
+    pub bounds: Bounds<Pixels>,
+    /// The content mask, already scaled to device pixels.
+    pub content_mask: ContentMask<Pixels>,
+    /// The destination corner radii, already scaled to device pixels.
+    pub corner_radii: Corners<Pixels>,
+    /// The transform in effect when this image was painted, from [`ElementContext::transform`].
+    pub transform: TransformationMatrix,
+    /// The source image data.
+    pub data: Arc<ImageData>,
+    /// The sub-rectangle of `data` to sample, in its own pixel space.
+    pub source_bounds: Bounds<Pixels>,
+    /// The texture sampler used to map destination pixels back to `source_bounds` texels.
+    pub interpolation: InterpolationMode,
+    /// How sampling extends past the edges of `source_bounds`.
+    pub tile: TileMode,
+    /// Whether to tint by the current text/foreground color (matching `MonochromeSprite`)
+    /// rather than painting the image's own colors (matching `PolychromeSprite`).
+    pub grayscale: bool,
+}
+
+impl ImageSprite {
+    /// The UV coordinates within `source_bounds` that `point` samples from, as a fraction of
+    /// `source_bounds`'s size with `tile` applied — the same mapping the fragment shader
+    /// performs per-fragment before multiplying by `source_bounds`'s extent to get texels.
+    pub fn sample_uv(&self, point: Point<Pixels>) -> (f32, f32) {
+        let relative = point - self.bounds.origin;
+        let u = relative.x.0 / self.bounds.size.width.0.max(f32::EPSILON);
+        let v = relative.y.0 / self.bounds.size.height.0.max(f32::EPSILON);
+        (apply_tile(u, self.tile), apply_tile(v, self.tile))
+    }
+}
+
+/// Wraps a `0.0..=1.0` UV coordinate `t` that has strayed outside that range, the same way
+/// the fragment shader's sampler would for each [`TileMode`].
+fn apply_tile(t: f32, tile: TileMode) -> f32 {
+    match tile {
+        TileMode::Clamp => t.clamp(0., 1.),
+        TileMode::Repeat => t.rem_euclid(1.),
+        TileMode::Mirror => {
+            let folded = t.rem_euclid(2.);
+            if folded > 1. {
+                2. - folded
+            } else {
+                folded
+            }
+        }
+    }
+}
+
+/// The width, caps, joins, and dash pattern used by [`ElementContext::paint_stroke`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    /// The width of the stroke, centered on the path.
+    pub width: Pixels,
+    /// How the stroke terminates at open ends.
+    pub cap: LineCap,
+    /// How the stroke bends at interior vertices.
+    pub join: LineJoin,
+    /// Alternating on/off lengths, walked cyclically by arc length starting at
+    /// `dash_offset`. An empty array means a solid (non-dashed) stroke.
+    pub dash_array: SmallVec<[Pixels; 4]>,
+    /// The arc-length offset into `dash_array` at which dashing begins.
+    pub dash_offset: Pixels,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: px(1.),
+            cap: LineCap::Butt,
+            join: LineJoin::Miter(4.),
+            dash_array: SmallVec::new(),
+            dash_offset: px(0.),
+        }
+    }
+}
+
+/// How a stroke terminates at an open end of a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// The stroke stops flush with the endpoint.
+    Butt,
+    /// The stroke is extended by a half-circle of radius `width / 2`.
+    Round,
+    /// The stroke is extended by a half-square of side `width / 2`.
+    Square,
+}
+
+/// How a stroke bends at an interior vertex of a path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet, unless the ratio of the miter's
+    /// length to `width` exceeds the given limit, in which case the join falls back to
+    /// [`LineJoin::Bevel`].
+    Miter(f32),
+    /// The outer corner is filled with a circular arc.
+    Round,
+    /// The outer corner is filled with a single triangle connecting the two edges.
+    Bevel,
+}
+
+/// A maximal run of a dashed polyline that should be stroked as one contiguous piece.
+struct DashSegment {
+    points: SmallVec<[Point<Pixels>; 4]>,
+    closed: bool,
+}
+
+/// Split `polyline` into the "on" runs of `style.dash_array`, walked cyclically by arc
+/// length starting at `style.dash_offset`. If `style.dash_array` is empty, returns the
+/// whole polyline unchanged as a single segment.
+fn dash_polyline(polyline: &[Point<Pixels>], closed: bool, style: &StrokeStyle) -> Vec<DashSegment> {
+    if style.dash_array.is_empty() {
+        return vec![DashSegment {
+            points: polyline.iter().copied().collect(),
+            closed,
+        }];
+    }
+
+    // Walk the polyline by arc length, toggling on/off at each cumulative dash boundary
+    // (wrapping around `dash_array`, starting at `dash_offset`) and splitting segments at
+    // the on/off boundaries they straddle.
+    let mut segments = Vec::new();
+    let mut current: SmallVec<[Point<Pixels>; 4]> = SmallVec::new();
+    let mut on = true;
+    let mut dash_index = 0;
+    let mut remaining = style.dash_array[0];
+    let mut offset = style.dash_offset;
+    while offset > px(0.) {
+        let step = remaining.min(offset);
+        offset -= step;
+        remaining -= step;
+        if remaining <= px(0.) {
+            dash_index = (dash_index + 1) % style.dash_array.len();
+            remaining = style.dash_array[dash_index];
+            on = !on;
+        }
+    }
+    if on {
+        current.push(polyline[0]);
+    }
+
+    let edges = if closed {
+        polyline.len()
+    } else {
+        polyline.len() - 1
+    };
+    for i in 0..edges {
+        let mut start = polyline[i];
+        let end = polyline[(i + 1) % polyline.len()];
+        let mut edge_length = (end - start).magnitude();
+        // Set whenever a dash boundary lands exactly on `end`: the toggle below already
+        // pushed `end` into `current`, so the trailing `current.push(end)` must not repeat it.
+        let mut end_already_pushed = false;
+        while edge_length > px(0.) {
+            let step = remaining.min(edge_length);
+            let t = 1. - (edge_length - step) / edge_length.max(px(f32::EPSILON));
+            let boundary = start + (end - start) * t;
+            edge_length -= step;
+            remaining -= step;
+            start = boundary;
+            if remaining <= px(0.) {
+                if on {
+                    current.push(boundary);
+                    segments.push(DashSegment {
+                        points: mem::take(&mut current),
+                        closed: false,
+                    });
+                } else {
+                    current.push(boundary);
+                }
+                end_already_pushed = edge_length <= px(0.);
+                dash_index = (dash_index + 1) % style.dash_array.len();
+                remaining = style.dash_array[dash_index];
+                on = !on;
+            }
+        }
+        if on && !end_already_pushed {
+            current.push(end);
+        }
+    }
+    if on && current.len() > 1 {
+        segments.push(DashSegment {
+            points: current,
+            closed: false,
+        });
+    }
+    segments
+}
+
+/// The number of stroked edges in a polyline of `point_count` points. A closed path wraps
+/// an extra edge from the last point back to the first (and a join at vertex 0), so it has
+/// one more edge than the same points stroked open.
+fn stroke_edge_count(point_count: usize, closed: bool) -> usize {
+    if closed {
+        point_count
+    } else {
+        point_count.saturating_sub(1)
+    }
+}
+
+/// Expand a polyline into a filled path representing its stroke outline, built from one
+/// quad per segment plus the join and cap polygons.
+///
+/// Each segment is offset by `±width / 2` along its normal; join geometry is emitted at
+/// interior vertices (bevel: one triangle, round: a triangular fan, miter: the extended
+/// edge intersection, falling back to a bevel past the miter limit); caps are emitted at
+/// the two open ends when `closed` is false.
+fn stroke_polyline_to_fill_path(
+    points: &[Point<Pixels>],
+    closed: bool,
+    style: &StrokeStyle,
+) -> Path<Pixels> {
+    let half_width = style.width / 2.;
+    let mut builder = Path::builder();
+    let point_count = points.len();
+    let edges = stroke_edge_count(point_count, closed);
+
+    for i in 0..edges {
+        let start = points[i];
+        let end = points[(i + 1) % point_count];
+        let direction = (end - start).normalize();
+        let normal = point(-direction.y, direction.x) * half_width;
+        builder.move_to(start + normal);
+        builder.line_to(end + normal);
+        builder.line_to(end - normal);
+        builder.line_to(start - normal);
+        builder.close();
+
+        if i + 1 < edges || closed {
+            let next_end = points[(i + 2) % point_count];
+            let next_direction = (next_end - end).normalize();
+            let next_normal = point(-next_direction.y, next_direction.x) * half_width;
+            emit_join(&mut builder, end, normal, next_normal, half_width, style.join);
+        }
+    }
+
+    if !closed {
+        if let (Some(&first), Some(&second)) = (points.first(), points.get(1)) {
+            emit_cap(&mut builder, first, second, half_width, style.cap);
+        }
+        if let (Some(&last), Some(&second_last)) = (points.last(), points.get(points.len() - 2)) {
+            emit_cap(&mut builder, last, second_last, half_width, style.cap);
+        }
+    }
+
+    builder.build()
+}
+
+/// Emit the join geometry connecting the stroke of the edge ending at `vertex` to the
+/// stroke of the edge beginning at `vertex`. `normal_in`/`normal_out` are the incoming and
+/// outgoing segments' normals (already scaled to `half_width`), the same vectors used to
+/// offset each segment's own rectangle in [`stroke_polyline_to_fill_path`].
+fn emit_join(
+    builder: &mut PathBuilder,
+    vertex: Point<Pixels>,
+    normal_in: Point<Pixels>,
+    normal_out: Point<Pixels>,
+    half_width: Pixels,
+    join: LineJoin,
+) {
+    // The two segments' offset rectangles already overlap on the inner side of the turn;
+    // only the outer side has a gap that needs join geometry. Which side is outer depends on
+    // the turn direction, given by the sign of the normals' cross product (a 90° rotation of
+    // both tangents preserves the sign of their cross product, so this matches the turn).
+    // Each normal is its tangent rotated by +90°, so a positive turn bends toward `+normal`
+    // and the outer side is `-normal`.
+    let turn = normal_in.x.0 * normal_out.y.0 - normal_in.y.0 * normal_out.x.0;
+    let side = if turn >= 0. { -1. } else { 1. };
+    let (outer_normal_in, outer_normal_out) = (normal_in * side, normal_out * side);
+    let (outer_in, outer_out) = (vertex + outer_normal_in, vertex + outer_normal_out);
+
+    match join {
+        LineJoin::Bevel => {
+            builder.move_to(vertex);
+            builder.line_to(outer_in);
+            builder.line_to(outer_out);
+            builder.close();
+        }
+        LineJoin::Round => {
+            builder.move_to(vertex);
+            builder.arc_to(vertex, half_width, outer_in, outer_out);
+            builder.close();
+        }
+        LineJoin::Miter(limit) => {
+            if let Some(miter_point) =
+                miter_intersection(vertex, outer_normal_in, outer_normal_out, half_width, limit)
+            {
+                builder.move_to(vertex);
+                builder.line_to(outer_in);
+                builder.line_to(miter_point);
+                builder.line_to(outer_out);
+                builder.close();
+            } else {
+                emit_join(builder, vertex, normal_in, normal_out, half_width, LineJoin::Bevel);
+            }
+        }
+    }
+}
+
+/// Emit the half-circle or half-square cap extending the stroke past the open endpoint
+/// `end`, whose adjacent path point is `prev`.
+fn emit_cap(builder: &mut PathBuilder, end: Point<Pixels>, prev: Point<Pixels>, half_width: Pixels, cap: LineCap) {
+    let direction = (end - prev).normalize();
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            let extension = direction * half_width;
+            let normal = point(-direction.y, direction.x) * half_width;
+            builder.move_to(end + normal);
+            builder.line_to(end + normal + extension);
+            builder.line_to(end - normal + extension);
+            builder.line_to(end - normal);
+            builder.close();
+        }
+        LineCap::Round => {
+            let normal = point(-direction.y, direction.x) * half_width;
+            builder.move_to(end);
+            builder.arc_to(end, half_width, end + normal, end - normal);
+            builder.close();
+        }
+    }
+}
+
+/// The point where the outer edges of the two segments meeting at `vertex` intersect, or
+/// `None` if the miter's length exceeds `limit * half_width`, in which case the join should
+/// fall back to a bevel. `normal_in`/`normal_out` are the incoming/outgoing segments'
+/// normals on the outer side of the turn, already scaled to `half_width`.
+///
+/// Each segment's offset line passes through `vertex + normal` at a right angle to the
+/// normal, so the miter tip — equidistant from both lines — lies along the bisector of the
+/// two *normals*, not the two segments' tangents (which point along the direction of travel
+/// and bisect a different angle entirely).
+fn miter_intersection(
+    vertex: Point<Pixels>,
+    normal_in: Point<Pixels>,
+    normal_out: Point<Pixels>,
+    half_width: Pixels,
+    limit: f32,
+) -> Option<Point<Pixels>> {
+    let bisector = point(
+        px(normal_in.x.0 + normal_out.x.0),
+        px(normal_in.y.0 + normal_out.y.0),
+    );
+    let bisector_length = bisector.magnitude();
+    if bisector_length.0 < f32::EPSILON {
+        return None;
+    }
+    // `bisector_length == 2 * half_width * cos(θ / 2)`, where θ is the angle between the two
+    // normals, so `half_width / cos(θ / 2)` is the distance along the bisector at which it
+    // crosses both offset lines.
+    let cos_half_angle = bisector_length.0 / (2. * half_width.0);
+    if cos_half_angle < f32::EPSILON {
+        return None;
+    }
+    let miter_length = half_width.0 / cos_half_angle;
+    if miter_length / half_width.0 > limit {
+        return None;
+    }
+    let scale = miter_length / bisector_length.0;
+    Some(point(
+        vertex.x + px(bisector.x.0 * scale),
+        vertex.y + px(bisector.y.0 * scale),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_gradient_parameter_is_zero_at_start_and_one_at_end() {
+        let start = point(px(0.), px(0.));
+        let end = point(px(10.), px(0.));
+        let gradient = Brush::LinearGradient {
+            start,
+            end,
+            stops: vec![],
+        };
+        assert_eq!(gradient_parameter(&gradient, start), 0.);
+        assert_eq!(gradient_parameter(&gradient, end), 1.);
+        assert_eq!(gradient_parameter(&gradient, point(px(5.), px(0.))), 0.5);
+    }
+
+    #[test]
+    fn radial_gradient_parameter_is_distance_over_radius() {
+        let gradient = Brush::RadialGradient {
+            center: point(px(0.), px(0.)),
+            radius: px(10.),
+            stops: vec![],
+        };
+        assert_eq!(
+            gradient_parameter(&gradient, point(px(0.), px(0.))),
+            0.
+        );
+        assert_eq!(
+            gradient_parameter(&gradient, point(px(10.), px(0.))),
+            1.
+        );
+    }
+
+    #[test]
+    fn sample_gradient_stops_clamps_outside_the_stop_range() {
+        let stops = vec![(0.25, red()), (0.75, blue())];
+        assert_eq!(sample_gradient_stops(&stops, 0.), red());
+        assert_eq!(sample_gradient_stops(&stops, 1.), blue());
+    }
+
+    #[test]
+    fn sample_gradient_stops_interpolates_between_bracketing_stops() {
+        let stops = vec![(0., red()), (1., red())];
+        assert_eq!(sample_gradient_stops(&stops, 0.5), red());
+    }
+
+    #[test]
+    fn dash_polyline_without_a_dash_array_returns_the_whole_line() {
+        let style = StrokeStyle::default();
+        let polyline = [point(px(0.), px(0.)), point(px(10.), px(0.))];
+        let segments = dash_polyline(&polyline, false, &style);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].points.as_slice(), &polyline);
+    }
+
+    #[test]
+    fn dash_polyline_splits_at_dash_boundaries() {
+        let mut style = StrokeStyle::default();
+        style.dash_array = smallvec::smallvec![px(2.), px(2.)];
+        let polyline = [point(px(0.), px(0.)), point(px(8.), px(0.))];
+        let segments = dash_polyline(&polyline, false, &style);
+        // on(0..2), off(2..4), on(4..6), off(6..8): two "on" runs survive.
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].points[0], point(px(0.), px(0.)));
+        assert_eq!(segments[0].points[1], point(px(2.), px(0.)));
+        assert_eq!(segments[1].points[0], point(px(4.), px(0.)));
+        assert_eq!(segments[1].points[1], point(px(6.), px(0.)));
+    }
+
+    #[test]
+    fn miter_intersection_meets_at_the_corner_of_a_right_angle_turn() {
+        // A (20, 0) -> B (10, 0) -> C (10, -10) turning 90 degrees at B, stroked with
+        // half_width 1: the outer corner of the turn is the intersection of the line y = 1
+        // (A-B's outer offset edge) and the line x = 9 (B-C's outer offset edge), which is
+        // (9, 1).
+        let vertex = point(px(10.), px(0.));
+        let normal_in = point(px(0.), px(1.));
+        let normal_out = point(px(-1.), px(0.));
+        let miter = miter_intersection(vertex, normal_in, normal_out, px(1.), 4.).unwrap();
+        assert!((miter.x.0 - 9.).abs() < 1e-4);
+        assert!((miter.y.0 - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn miter_intersection_falls_back_to_none_past_the_limit() {
+        // A near-180-degree turn produces an arbitrarily long miter, which should be
+        // rejected by any finite limit.
+        let vertex = point(px(10.), px(0.));
+        let normal_in = point(px(0.), px(1.));
+        let normal_out = point(px(0.), px(-0.999));
+        assert_eq!(
+            miter_intersection(vertex, normal_in, normal_out, px(1.), 4.),
+            None
+        );
+    }
+
+    #[test]
+    fn stroke_edge_count_includes_the_closing_edge_when_closed() {
+        assert_eq!(stroke_edge_count(4, false), 3);
+        assert_eq!(stroke_edge_count(4, true), 4);
+    }
+
+    #[test]
+    fn apply_tile_clamps_to_the_source_edge() {
+        assert_eq!(apply_tile(-0.5, TileMode::Clamp), 0.);
+        assert_eq!(apply_tile(1.5, TileMode::Clamp), 1.);
+        assert_eq!(apply_tile(0.25, TileMode::Clamp), 0.25);
+    }
+
+    #[test]
+    fn apply_tile_repeats_past_the_source_edge() {
+        assert_eq!(apply_tile(1.25, TileMode::Repeat), 0.25);
+        assert_eq!(apply_tile(-0.25, TileMode::Repeat), 0.75);
+    }
+
+    #[test]
+    fn apply_tile_mirrors_past_the_source_edge() {
+        assert_eq!(apply_tile(1.25, TileMode::Mirror), 0.75);
+        assert_eq!(apply_tile(0.75, TileMode::Mirror), 0.75);
+        assert_eq!(apply_tile(2.25, TileMode::Mirror), 0.25);
+    }
+
+    fn red() -> Hsla {
+        Hsla {
+            h: 0.,
+            s: 1.,
+            l: 0.5,
+            a: 1.,
+        }
+    }
+
+    fn blue() -> Hsla {
+        Hsla {
+            h: 0.6667,
+            s: 1.,
+            l: 0.5,
+            a: 1.,
+        }
+    }
+}